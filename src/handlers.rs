@@ -1,50 +1,281 @@
-use crate::repositories::{CreateTodo, TodoRepository, UpdateTodo};
+use crate::repositories::{
+    CreateLabel, CreateTodo, Label, LabelRepository, ListOptions, RepositoryError, SearchOptions,
+    Todo, TodoRepository, UpdateTodo,
+};
 use axum::{
-    extract::{Extension, Path},
+    async_trait,
+    extract::{Extension, FromRequest, Path, Query, RequestParts},
     response::IntoResponse,
-    Json,
+    BoxError, Json,
 };
 use hyper::StatusCode;
+use serde::de::DeserializeOwned;
 use std::sync::Arc;
+use validator::Validate;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+// `axum::Json` をラップし、デシリアライズ後に `validate()` を走らせる抽出子。
+// 制約に反した入力は `422 Unprocessable Entity` とフィールドエラーで弾く
+#[derive(Debug)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    B: http_body::Body + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req).await.map_err(|rejection| {
+            (StatusCode::BAD_REQUEST, format!("Json parse error: {}", rejection))
+        })?;
+        value.validate().map_err(|rejection| {
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Validation error: {}", rejection).replace('\n', ", "),
+            )
+        })?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+// リポジトリのエラーを HTTP ステータスに写す。存在しない id のみ 404 とし、
+// 接続やトランザクションの失敗などは 500 として正直に返す
+fn not_found_or_500(err: anyhow::Error) -> StatusCode {
+    match err.downcast_ref::<RepositoryError>() {
+        Some(RepositoryError::NotFound(_)) => StatusCode::NOT_FOUND,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// CRUD ハンドラの契約をまとめた OpenAPI ドキュメント
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_todo,
+        find_todo,
+        all_todo,
+        search_todo,
+        update_todo,
+        delete_todo,
+    ),
+    components(schemas(Todo, Label, CreateTodo, UpdateTodo))
+)]
+pub struct ApiDoc;
+
+// `/swagger-ui` の対話 UI と `/api-doc/openapi.json` の生成物を提供する
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi())
+}
 
+#[utoipa::path(
+    post,
+    path = "/todos",
+    request_body = CreateTodo,
+    responses(
+        (status = 201, description = "Todo created", body = Todo),
+        (status = 422, description = "Validation error"),
+        (status = 500, description = "Failed to persist todo")
+    )
+)]
 pub async fn create_todo<T: TodoRepository>(
-    Json(payload): Json<CreateTodo>,
+    ValidatedJson(payload): ValidatedJson<CreateTodo>,
     Extension(repository): Extension<Arc<T>>,
-) -> impl IntoResponse {
-    let create = repository.create(payload);
-    let todo = create;
+) -> Result<impl IntoResponse, StatusCode> {
+    let todo = repository
+        .create(payload)
+        .await
+        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
 
-    (StatusCode::CREATED, Json(todo))
+    Ok((StatusCode::CREATED, Json(todo)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/todos/{id}",
+    params(("id" = i32, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Todo found", body = Todo),
+        (status = 404, description = "Todo not found")
+    )
+)]
 pub async fn find_todo<T: TodoRepository>(
     Path(id): Path<i32>,
     Extension(repository): Extension<Arc<T>>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let todo = repository.find(id).ok_or(StatusCode::NOT_FOUND)?;
+    let todo = repository
+        .find(id)
+        .await
+        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
     Ok((StatusCode::OK, Json(todo)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/todos",
+    params(
+        ("offset" = Option<usize>, Query, description = "Number of items to skip"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of items to return"),
+        ("completed" = Option<bool>, Query, description = "Filter by completion state")
+    ),
+    responses((status = 200, description = "List todos", body = [Todo]))
+)]
 pub async fn all_todo<T: TodoRepository>(
+    Query(options): Query<ListOptions>,
+    Extension(repository): Extension<Arc<T>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let todos = repository
+        .all(options)
+        .await
+        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    Ok((StatusCode::OK, Json(todos)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/todos/search",
+    params(
+        ("q" = String, Query, description = "Keyword to match against todo text"),
+        ("offset" = Option<usize>, Query, description = "Number of items to skip"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of items to return")
+    ),
+    responses((status = 200, description = "Matching todos ranked by match position", body = [Todo]))
+)]
+pub async fn search_todo<T: TodoRepository>(
+    Query(options): Query<SearchOptions>,
     Extension(repository): Extension<Arc<T>>,
-) -> impl IntoResponse {
-    todo!();
+) -> Result<impl IntoResponse, StatusCode> {
+    let todos = repository
+        .search(options)
+        .await
+        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    Ok((StatusCode::OK, Json(todos)))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/todos/{id}",
+    request_body = UpdateTodo,
+    params(("id" = i32, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Todo updated", body = Todo),
+        (status = 404, description = "Todo not found"),
+        (status = 422, description = "Validation error")
+    )
+)]
 pub async fn update_todo<T: TodoRepository>(
     Path(id): Path<i32>,
-    Json(payload): Json<UpdateTodo>,
+    ValidatedJson(payload): ValidatedJson<UpdateTodo>,
     Extension(repository): Extension<Arc<T>>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    todo!();
+    let todo = repository.update(id, payload).await.map_err(not_found_or_500)?;
 
-    Ok(StatusCode::OK)
+    Ok((StatusCode::OK, Json(todo)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/todos/{id}",
+    params(("id" = i32, Path, description = "Todo id")),
+    responses(
+        (status = 204, description = "Todo deleted"),
+        (status = 404, description = "Todo not found")
+    )
+)]
 pub async fn delete_todo<T: TodoRepository>(
     Path(id): Path<i32>,
     Extension(repository): Extension<Arc<T>>,
+) -> Result<StatusCode, StatusCode> {
+    repository
+        .delete(id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(not_found_or_500)
+}
+
+pub async fn create_label<T: LabelRepository>(
+    Json(payload): Json<CreateLabel>,
+    Extension(repository): Extension<Arc<T>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let label = repository
+        .create(payload)
+        .await
+        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    Ok((StatusCode::CREATED, Json(label)))
+}
+
+pub async fn all_label<T: LabelRepository>(
+    Extension(repository): Extension<Arc<T>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let labels = repository
+        .all()
+        .await
+        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    Ok((StatusCode::OK, Json(labels)))
+}
+
+pub async fn delete_label<T: LabelRepository>(
+    Path(id): Path<i32>,
+    Extension(repository): Extension<Arc<T>>,
 ) -> StatusCode {
-    todo!();
+    repository
+        .delete(id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .unwrap_or(StatusCode::NOT_FOUND)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::repositories::{CreateTodo, UpdateTodo};
+    use axum::body::Body;
+    use axum::http::Request;
+
+    async fn extract<T>(body: &str) -> Result<ValidatedJson<T>, (StatusCode, String)>
+    where
+        T: DeserializeOwned + Validate,
+    {
+        let req = Request::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let mut parts = RequestParts::new(req);
+        ValidatedJson::<T>::from_request(&mut parts).await
+    }
+
+    #[tokio::test]
+    async fn validated_json_rejects_empty_and_oversized_text() {
+        let long = "a".repeat(101);
+
+        // CreateTodo: 空文字も超過も 422 で弾く
+        for text in ["", long.as_str()] {
+            let body = format!(r#"{{"text":"{}"}}"#, text);
+            let (status, _) = extract::<CreateTodo>(&body).await.err().unwrap();
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+
+        // 妥当な長さは通る
+        assert!(extract::<CreateTodo>(r#"{"text":"ok"}"#).await.is_ok());
+
+        // UpdateTodo: text を与えた場合のみ同じ制約が効く
+        let (status, _) = extract::<UpdateTodo>(r#"{"text":""}"#).await.err().unwrap();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let body = format!(r#"{{"text":"{}"}}"#, long);
+        let (status, _) = extract::<UpdateTodo>(&body).await.err().unwrap();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        // text 省略（None）は検証対象にならず通る
+        assert!(extract::<UpdateTodo>(r#"{"completed":true}"#).await.is_ok());
+    }
 }
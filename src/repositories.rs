@@ -1,4 +1,9 @@
+use anyhow::Context;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use validator::Validate;
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
@@ -6,59 +11,160 @@ use std::{
 use thiserror::Error;
 
 #[derive(Debug, Error)]
-enum RepositoryError {
+pub enum RepositoryError {
     #[error("NotFound, id is {0}")]
     NotFound(i32),
 }
 
 // トレイトを実装することで、そのトレイトのメソッドを実装することが強制される
+#[async_trait]
 pub trait TodoRepository: Clone + std::marker::Send + std::marker::Sync + 'static {
-    fn create(&self, payload: CreateTodo) -> Todo;
-    fn find(&self, id: i32) -> Option<Todo>;
-    fn all(&self) -> Vec<Todo>;
-    fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<Todo>;
-    fn delete(&self, id: i32) -> anyhow::Result<()>;
+    async fn create(&self, payload: CreateTodo) -> anyhow::Result<Todo>;
+    async fn find(&self, id: i32) -> anyhow::Result<Option<Todo>>;
+    async fn all(&self, options: ListOptions) -> anyhow::Result<Vec<Todo>>;
+    async fn search(&self, options: SearchOptions) -> anyhow::Result<Vec<Todo>>;
+    async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<Todo>;
+    async fn delete(&self, id: i32) -> anyhow::Result<()>;
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, ToSchema)]
 pub struct Todo {
     id: i32,
     text: String,
     completed: bool,
+    labels: Vec<Label>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, ToSchema, sqlx::FromRow)]
+pub struct Label {
+    id: i32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, ToSchema, Validate)]
 pub struct CreateTodo {
+    #[validate(length(min = 1, max = 100, message = "Can not be empty or longer than 100 chars"))]
     text: String,
+    #[serde(default)]
+    labels: Vec<i32>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, ToSchema, Validate)]
 pub struct UpdateTodo {
+    #[validate(length(min = 1, max = 100, message = "Can not be empty or longer than 100 chars"))]
     text: Option<String>,
     completed: Option<bool>,
+    labels: Option<Vec<i32>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct CreateLabel {
+    name: String,
+}
+
+// `all` 一覧のページングと絞り込みのためのクエリパラメータ
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ListOptions {
+    offset: Option<usize>,
+    limit: Option<usize>,
+    completed: Option<bool>,
+}
+
+// `search` のキーワードとページング。`Query`(serde_urlencoded)は
+// `#[serde(flatten)]` を扱えないため、`ListOptions` と同様にフラットに持つ
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SearchOptions {
+    q: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
 }
 
 impl Todo {
-    pub fn new(id: i32, text: String) -> Self {
+    pub fn new(id: i32, text: String, labels: Vec<Label>) -> Self {
         Self {
             id,
             text,
             completed: false,
+            labels,
         }
     }
 }
 
+impl Label {
+    pub fn new(id: i32, name: String) -> Self {
+        Self { id, name }
+    }
+}
+
+// ラベル自体の作成・一覧・削除を扱うトレイト。`TodoRepository` と同じ構成で揃える。
+// todo へのラベルの付け外しは `TodoRepository` の create / update が行う
+#[async_trait]
+pub trait LabelRepository: Clone + std::marker::Send + std::marker::Sync + 'static {
+    async fn create(&self, payload: CreateLabel) -> anyhow::Result<Label>;
+    async fn all(&self) -> anyhow::Result<Vec<Label>>;
+    async fn delete(&self, id: i32) -> anyhow::Result<()>;
+}
+
+type LabelDatas = HashMap<i32, Label>;
+
+#[derive(Clone, Debug)]
+pub struct LabelRepositoryForMemory {
+    store: Arc<RwLock<LabelDatas>>,
+}
+
+impl LabelRepositoryForMemory {
+    pub fn new() -> Self {
+        LabelRepositoryForMemory {
+            store: Arc::default(),
+        }
+    }
+
+    fn write_store_ref(&self) -> RwLockWriteGuard<LabelDatas> {
+        self.store.write().unwrap()
+    }
+
+    fn read_store_ref(&self) -> RwLockReadGuard<LabelDatas> {
+        self.store.read().unwrap()
+    }
+}
+
+#[async_trait]
+impl LabelRepository for LabelRepositoryForMemory {
+    async fn create(&self, payload: CreateLabel) -> anyhow::Result<Label> {
+        let mut store = self.write_store_ref();
+        let id = (store.len() + 1) as i32;
+        let label = Label::new(id, payload.name);
+        store.insert(id, label.clone());
+        Ok(label)
+    }
+
+    async fn all(&self) -> anyhow::Result<Vec<Label>> {
+        let store = self.read_store_ref();
+        Ok(store.values().cloned().collect())
+    }
+
+    async fn delete(&self, id: i32) -> anyhow::Result<()> {
+        let mut store = self.write_store_ref();
+        store.remove(&id).context(RepositoryError::NotFound(id))?;
+        Ok(())
+    }
+}
+
 type TodoDatas = HashMap<i32, Todo>;
 
 #[derive(Clone, Debug)]
 pub struct TodoRepositoryForMemory {
     store: Arc<RwLock<TodoDatas>>,
+    // 名前を解決するために同じプロセスのラベルストアを共有する。
+    // DB 実装の `where id = any($2)` と同じく、存在しない id は黙って捨てる
+    labels: LabelRepositoryForMemory,
 }
 
 impl TodoRepositoryForMemory {
-    pub fn new() -> Self {
+    pub fn new(labels: LabelRepositoryForMemory) -> Self {
         TodoRepositoryForMemory {
             store: Arc::default(),
+            labels,
         }
     }
 
@@ -69,30 +175,556 @@ impl TodoRepositoryForMemory {
     fn read_store_ref(&self) -> RwLockReadGuard<TodoDatas> {
         self.store.read().unwrap()
     }
+
+    // 渡されたラベル id をラベルストアの実体（名前付き）に引き直す
+    fn resolve_labels(&self, ids: &[i32]) -> Vec<Label> {
+        let store = self.labels.read_store_ref();
+        ids.iter().filter_map(|id| store.get(id).cloned()).collect()
+    }
 }
 
+#[async_trait]
 impl TodoRepository for TodoRepositoryForMemory {
-    fn create(&self, payload: CreateTodo) -> Todo {
+    async fn create(&self, payload: CreateTodo) -> anyhow::Result<Todo> {
         let mut store = self.write_store_ref();
         let id = (store.len() + 1) as i32;
-        let todo = Todo::new(id, payload.text.clone());
+        let labels = self.resolve_labels(&payload.labels);
+        let todo = Todo::new(id, payload.text.clone(), labels);
         store.insert(id, todo.clone());
-        todo
+        Ok(todo)
     }
 
-    fn find(&self, id: i32) -> Option<Todo> {
-        todo!();
+    async fn find(&self, id: i32) -> anyhow::Result<Option<Todo>> {
+        let store = self.read_store_ref();
+        Ok(store.get(&id).cloned())
     }
 
-    fn all(&self) -> Vec<Todo> {
-        todo!();
+    async fn all(&self, options: ListOptions) -> anyhow::Result<Vec<Todo>> {
+        let store = self.read_store_ref();
+        let mut todos: Vec<Todo> = store.values().cloned().collect();
+        todos.sort_by_key(|todo| todo.id);
+        Ok(todos
+            .into_iter()
+            .filter(|todo| match options.completed {
+                Some(completed) => todo.completed == completed,
+                None => true,
+            })
+            .skip(options.offset.unwrap_or(0))
+            .take(options.limit.unwrap_or(usize::MAX))
+            .collect())
     }
 
-    fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<Todo> {
-        todo!();
+    async fn search(&self, options: SearchOptions) -> anyhow::Result<Vec<Todo>> {
+        let store = self.read_store_ref();
+        let needle = options.q.to_lowercase();
+        // text 中にキーワードを含む todo を集め、マッチ開始位置を控えておく
+        let mut matched: Vec<(usize, Todo)> = store
+            .values()
+            .filter_map(|todo| {
+                todo.text
+                    .to_lowercase()
+                    .find(&needle)
+                    .map(|pos| (pos, todo.clone()))
+            })
+            .collect();
+        // マッチ位置が前にあるものほど上位、同順位は id 昇順で安定させる
+        matched.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.id.cmp(&b.1.id)));
+        Ok(matched
+            .into_iter()
+            .map(|(_, todo)| todo)
+            .skip(options.offset.unwrap_or(0))
+            .take(options.limit.unwrap_or(usize::MAX))
+            .collect())
     }
 
-    fn delete(&self, id: i32) -> anyhow::Result<()> {
-        todo!();
+    async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<Todo> {
+        let mut store = self.write_store_ref();
+        let todo = store.get(&id).context(RepositoryError::NotFound(id))?;
+        let text = payload.text.unwrap_or_else(|| todo.text.clone());
+        let completed = payload.completed.unwrap_or(todo.completed);
+        // ラベル id が渡された場合のみ関連付けを作り直す
+        let labels = match payload.labels {
+            Some(ref ids) => self.resolve_labels(ids),
+            None => todo.labels.clone(),
+        };
+        let todo = Todo {
+            id,
+            text,
+            completed,
+            labels,
+        };
+        store.insert(id, todo.clone());
+        Ok(todo)
+    }
+
+    async fn delete(&self, id: i32) -> anyhow::Result<()> {
+        let mut store = self.write_store_ref();
+        store.remove(&id).context(RepositoryError::NotFound(id))?;
+        Ok(())
+    }
+}
+
+// todos を labels と LEFT JOIN した 1 行に対応する中間表現。
+// 同じ todo が複数行に分かれて返るので `fold_entities` で畳み込む
+#[derive(sqlx::FromRow)]
+struct TodoWithLabelFromRow {
+    id: i32,
+    text: String,
+    completed: bool,
+    label_id: Option<i32>,
+    label_name: Option<String>,
+}
+
+fn fold_entities(rows: Vec<TodoWithLabelFromRow>) -> Vec<Todo> {
+    let mut accum: Vec<Todo> = Vec::new();
+    for row in rows {
+        let label = match (row.label_id, row.label_name) {
+            (Some(id), Some(name)) => Some(Label::new(id, name)),
+            _ => None,
+        };
+        if let Some(todo) = accum.iter_mut().find(|t| t.id == row.id) {
+            if let Some(label) = label {
+                todo.labels.push(label);
+            }
+            continue;
+        }
+        accum.push(Todo {
+            id: row.id,
+            text: row.text,
+            completed: row.completed,
+            labels: label.into_iter().collect(),
+        });
+    }
+    accum
+}
+
+#[derive(Clone)]
+pub struct TodoRepositoryForDb {
+    pool: PgPool,
+}
+
+impl TodoRepositoryForDb {
+    pub fn new(pool: PgPool) -> Self {
+        TodoRepositoryForDb { pool }
+    }
+}
+
+const TODO_WITH_LABEL_SELECT: &str = r#"
+select todos.*, labels.id as label_id, labels.name as label_name
+from todos
+left outer join todo_labels tl on todos.id = tl.todo_id
+left outer join labels on labels.id = tl.label_id
+"#;
+
+#[async_trait]
+impl TodoRepository for TodoRepositoryForDb {
+    async fn create(&self, payload: CreateTodo) -> anyhow::Result<Todo> {
+        // todo 本体とラベル関連付けは 1 トランザクションで入れる
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query_as::<_, TodoFromRow>(
+            r#"
+insert into todos (text, completed)
+values ($1, false)
+returning *
+            "#,
+        )
+        .bind(payload.text)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+insert into todo_labels (todo_id, label_id)
+select $1, id from labels where id = any($2)
+            "#,
+        )
+        .bind(row.id)
+        .bind(&payload.labels)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        self.find(row.id)
+            .await?
+            .context(RepositoryError::NotFound(row.id))
+    }
+
+    async fn find(&self, id: i32) -> anyhow::Result<Option<Todo>> {
+        let rows = sqlx::query_as::<_, TodoWithLabelFromRow>(&format!(
+            "{} where todos.id = $1",
+            TODO_WITH_LABEL_SELECT
+        ))
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(fold_entities(rows).into_iter().next())
+    }
+
+    async fn all(&self, options: ListOptions) -> anyhow::Result<Vec<Todo>> {
+        // 対象となる todo id をページング済みで先に絞り込み、
+        // その id 集合に対してラベルを JOIN する（JOIN で行が増えても件数がぶれない）
+        let sql = format!(
+            r#"
+{select}
+where todos.id in (
+    select id from todos
+    where ($3::bool is null or completed = $3)
+    order by id asc
+    offset $1 limit $2
+)
+order by todos.id asc
+            "#,
+            select = TODO_WITH_LABEL_SELECT
+        );
+        let rows = sqlx::query_as::<_, TodoWithLabelFromRow>(&sql)
+            .bind(options.offset.unwrap_or(0) as i64)
+            .bind(options.limit.map(|l| l as i64).unwrap_or(i64::MAX))
+            .bind(options.completed)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(fold_entities(rows))
+    }
+
+    async fn search(&self, options: SearchOptions) -> anyhow::Result<Vec<Todo>> {
+        // キーワードは `ILIKE` による大文字小文字を無視した部分一致で引く。
+        // `%` `_` `\` をエスケープし、インメモリ実装と同じリテラル部分一致に揃える。
+        // 並びもインメモリ実装と同じく、マッチ位置の早い順、同位は id 昇順
+        let escaped = options
+            .q
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+        let sql = format!(
+            r#"
+{select}
+where todos.id in (
+    select id from todos
+    where text ilike $1 escape '\'
+    order by strpos(lower(text), lower($4)) asc, id asc
+    offset $2 limit $3
+)
+order by strpos(lower(todos.text), lower($4)) asc, todos.id asc
+            "#,
+            select = TODO_WITH_LABEL_SELECT
+        );
+        let rows = sqlx::query_as::<_, TodoWithLabelFromRow>(&sql)
+            .bind(pattern)
+            .bind(options.offset.unwrap_or(0) as i64)
+            .bind(options.limit.map(|l| l as i64).unwrap_or(i64::MAX))
+            .bind(options.q)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(fold_entities(rows))
+    }
+
+    async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<Todo> {
+        let old = self
+            .find(id)
+            .await?
+            .context(RepositoryError::NotFound(id))?;
+        let text = payload.text.unwrap_or(old.text);
+        let completed = payload.completed.unwrap_or(old.completed);
+        // todo 本体とラベル関連付けは create と同様に 1 トランザクションで入れ替える
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+update todos set text = $1, completed = $2 where id = $3
+            "#,
+        )
+        .bind(text)
+        .bind(completed)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        // ラベル id が渡された場合のみ関連付けを作り直す
+        if let Some(ids) = payload.labels {
+            sqlx::query(r#"delete from todo_labels where todo_id = $1"#)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                r#"
+insert into todo_labels (todo_id, label_id)
+select $1, id from labels where id = any($2)
+                "#,
+            )
+            .bind(id)
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        self.find(id)
+            .await?
+            .context(RepositoryError::NotFound(id))
+    }
+
+    async fn delete(&self, id: i32) -> anyhow::Result<()> {
+        sqlx::query(r#"delete from todo_labels where todo_id = $1"#)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        // execute は行が無くてもエラーにならないため、影響行数で存在を判定する
+        let done = sqlx::query(
+            r#"
+delete from todos where id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        if done.rows_affected() == 0 {
+            return Err(anyhow::Error::new(RepositoryError::NotFound(id)));
+        }
+        Ok(())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TodoFromRow {
+    id: i32,
+    #[allow(dead_code)]
+    text: String,
+    #[allow(dead_code)]
+    completed: bool,
+}
+
+#[derive(Clone)]
+pub struct LabelRepositoryForDb {
+    pool: PgPool,
+}
+
+impl LabelRepositoryForDb {
+    pub fn new(pool: PgPool) -> Self {
+        LabelRepositoryForDb { pool }
+    }
+}
+
+#[async_trait]
+impl LabelRepository for LabelRepositoryForDb {
+    async fn create(&self, payload: CreateLabel) -> anyhow::Result<Label> {
+        let label = sqlx::query_as::<_, Label>(
+            r#"
+insert into labels (name) values ($1) returning *
+            "#,
+        )
+        .bind(payload.name)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(label)
+    }
+
+    async fn all(&self) -> anyhow::Result<Vec<Label>> {
+        let labels = sqlx::query_as::<_, Label>(r#"select * from labels order by id asc"#)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(labels)
+    }
+
+    async fn delete(&self, id: i32) -> anyhow::Result<()> {
+        sqlx::query(r#"delete from labels where id = $1"#)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn seed(repo: &TodoRepositoryForMemory, text: &str) -> Todo {
+        repo.create(CreateTodo {
+            text: text.to_string(),
+            labels: vec![],
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn all_sorts_by_id_then_filters_and_paginates() {
+        let repo = TodoRepositoryForMemory::new(LabelRepositoryForMemory::new());
+        let t1 = seed(&repo, "one").await;
+        let t2 = seed(&repo, "two").await;
+        let t3 = seed(&repo, "three").await;
+        repo.update(
+            t2.id,
+            UpdateTodo {
+                text: None,
+                completed: Some(true),
+                labels: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        // id 昇順で全件返る
+        let all = repo.all(ListOptions::default()).await.unwrap();
+        assert_eq!(
+            all.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![t1.id, t2.id, t3.id]
+        );
+
+        // completed フィルタ
+        let done = repo
+            .all(ListOptions {
+                offset: None,
+                limit: None,
+                completed: Some(true),
+            })
+            .await
+            .unwrap();
+        assert_eq!(done.iter().map(|t| t.id).collect::<Vec<_>>(), vec![t2.id]);
+        let open = repo
+            .all(ListOptions {
+                offset: None,
+                limit: None,
+                completed: Some(false),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            open.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![t1.id, t3.id]
+        );
+
+        // offset / limit によるページング
+        let page = repo
+            .all(ListOptions {
+                offset: Some(1),
+                limit: Some(1),
+                completed: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), vec![t2.id]);
+    }
+
+    #[tokio::test]
+    async fn search_ranks_by_match_position_then_id() {
+        let repo = TodoRepositoryForMemory::new(LabelRepositoryForMemory::new());
+        let t1 = seed(&repo, "foo alpha").await; // マッチ位置 0
+        let t2 = seed(&repo, "foo beta").await; // マッチ位置 0（同順位 → id で決着）
+        let t3 = seed(&repo, "a foo").await; // マッチ位置 2
+        let _miss = seed(&repo, "nothing here").await; // 不一致
+
+        // 大文字小文字を無視し、マッチ位置の早い順、同位は id 昇順
+        let results = repo
+            .search(SearchOptions {
+                q: "FOO".to_string(),
+                offset: None,
+                limit: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            results.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![t1.id, t2.id, t3.id]
+        );
+
+        // ページングは all と同じく offset / limit で行う
+        let page = repo
+            .search(SearchOptions {
+                q: "foo".to_string(),
+                offset: Some(1),
+                limit: Some(1),
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), vec![t2.id]);
+    }
+
+    #[tokio::test]
+    async fn create_attaches_labels_with_resolved_names() {
+        let labels = LabelRepositoryForMemory::new();
+        let work = labels
+            .create(CreateLabel {
+                name: "work".to_string(),
+            })
+            .await
+            .unwrap();
+        let home = labels
+            .create(CreateLabel {
+                name: "home".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let repo = TodoRepositoryForMemory::new(labels);
+        let created = repo
+            .create(CreateTodo {
+                text: "buy milk".to_string(),
+                labels: vec![work.id, home.id, 999], // 999 は存在しないので捨てられる
+            })
+            .await
+            .unwrap();
+
+        // 名前まで解決され、未知の id は含まれない
+        assert_eq!(created.labels, vec![work.clone(), home.clone()]);
+        // find / all も結合済みラベルを返す
+        let found = repo.find(created.id).await.unwrap().unwrap();
+        assert_eq!(found.labels, vec![work.clone(), home.clone()]);
+        let all = repo.all(ListOptions::default()).await.unwrap();
+        assert_eq!(all[0].labels, vec![work, home]);
+    }
+
+    #[tokio::test]
+    async fn update_reconciles_label_associations() {
+        let labels = LabelRepositoryForMemory::new();
+        let work = labels
+            .create(CreateLabel {
+                name: "work".to_string(),
+            })
+            .await
+            .unwrap();
+        let home = labels
+            .create(CreateLabel {
+                name: "home".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let repo = TodoRepositoryForMemory::new(labels);
+        let todo = repo
+            .create(CreateTodo {
+                text: "task".to_string(),
+                labels: vec![work.id],
+            })
+            .await
+            .unwrap();
+        assert_eq!(todo.labels, vec![work.clone()]);
+
+        // labels を渡すと関連付けを作り直す
+        let updated = repo
+            .update(
+                todo.id,
+                UpdateTodo {
+                    text: None,
+                    completed: None,
+                    labels: Some(vec![home.id]),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.labels, vec![home.clone()]);
+
+        // labels を渡さないと既存の関連付けを保つ
+        let kept = repo
+            .update(
+                todo.id,
+                UpdateTodo {
+                    text: Some("renamed".to_string()),
+                    completed: None,
+                    labels: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(kept.labels, vec![home]);
     }
 }